@@ -0,0 +1,252 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_storage::MerkleTreeLedger;
+use snarkvm_utilities::{bytes::ToBytes, to_bytes};
+
+use blake2::{digest::Digest, Blake2s256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    ops::ControlFlow,
+    time::Instant,
+};
+
+/// The content-addressed id of a transaction, derived from the hash of its serialized bytes.
+pub type Txid = Vec<u8>;
+
+/// Scales a fee rate (a fraction of `fee / size_in_bytes`) up into an integer key, so it can be
+/// used as a `BTreeMap` key without losing the precision a plain integer division would throw away.
+const FEE_RATE_SCALE: u64 = 1_000_000;
+
+/// Computes the sorting key used for `MemoryPool`'s fee-rate index: higher is more profitable.
+/// A zero-size entry (which shouldn't occur in practice) sorts lowest rather than panicking.
+fn fee_rate_key(fee: i64, size_in_bytes: usize) -> u64 {
+    if size_in_bytes == 0 {
+        return 0;
+    }
+
+    ((fee.max(0) as u128 * FEE_RATE_SCALE as u128) / size_in_bytes as u128) as u64
+}
+
+/// Hashes `transaction`'s serialized bytes to derive its `Txid`.
+fn compute_txid<T: ToBytes>(transaction: &T) -> Result<Txid, MemoryPoolError> {
+    let bytes =
+        to_bytes![transaction].map_err(|error| MemoryPoolError(format!("failed to serialize transaction: {}", error)))?;
+
+    Ok(Blake2s256::digest(&bytes).to_vec())
+}
+
+/// An error produced by the memory pool.
+#[derive(Debug)]
+pub struct MemoryPoolError(String);
+
+impl fmt::Display for MemoryPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for MemoryPoolError {}
+
+/// A transaction held in the memory pool, along with the bookkeeping `MemoryPool` needs to
+/// order it without depending on `T`'s internal fields.
+#[derive(Clone)]
+pub struct Entry<T> {
+    /// The serialized size of `transaction`, in bytes.
+    pub size_in_bytes: usize,
+    /// The transaction's fee, supplied by the caller so this module doesn't need to know how to
+    /// compute it from `T`.
+    pub fee: i64,
+    /// The transaction itself.
+    pub transaction: T,
+}
+
+/// An unconfirmed-transaction pool, keyed by `Txid` and indexed by fee rate for candidate
+/// selection.
+pub struct MemoryPool<T> {
+    transactions: HashMap<Txid, Entry<T>>,
+    /// Mirrors `transactions`, ordered by `(fee_rate, txid)` so the highest fee-rate entries can
+    /// be walked in order without re-sorting `transactions` on every call.
+    fee_index: BTreeMap<(u64, Txid), ()>,
+    arrivals: HashMap<Txid, Instant>,
+    total_size_in_bytes: usize,
+}
+
+impl<T> Default for MemoryPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MemoryPool<T> {
+    /// Creates a new, empty `MemoryPool`.
+    pub fn new() -> Self {
+        Self {
+            transactions: HashMap::new(),
+            fee_index: BTreeMap::new(),
+            arrivals: HashMap::new(),
+            total_size_in_bytes: 0,
+        }
+    }
+
+    /// Returns `true` if `txid` is currently held in the pool.
+    pub fn contains(&self, txid: &Txid) -> bool {
+        self.transactions.contains_key(txid)
+    }
+
+    /// Returns the entry for `txid`, if it's currently held in the pool.
+    pub fn get(&self, txid: &Txid) -> Option<&Entry<T>> {
+        self.transactions.get(txid)
+    }
+
+    /// Walks the pool's entries highest-fee-rate-first, invoking `visit` on each until either
+    /// `max_total_size` would be exceeded or `visit` returns `ControlFlow::Break`. An entry that
+    /// would push the running total over `max_total_size` stops the walk rather than being
+    /// skipped; smaller, lower fee-rate entries behind it are not visited even if they would fit.
+    pub fn iterate_candidates<F>(&self, max_total_size: usize, mut visit: F)
+    where
+        F: FnMut(&Entry<T>) -> ControlFlow<()>,
+    {
+        let mut total_size = 0usize;
+
+        for (_, txid) in self.fee_index.iter().rev() {
+            let entry = match self.transactions.get(txid) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            if total_size + entry.size_in_bytes > max_total_size {
+                break;
+            }
+
+            match visit(entry) {
+                ControlFlow::Continue(()) => total_size += entry.size_in_bytes,
+                ControlFlow::Break(()) => break,
+            }
+        }
+    }
+
+    /// Removes and returns the entry for `txid`, if present, updating the fee index, arrival
+    /// table and running size total to match.
+    fn remove(&mut self, txid: &Txid) -> Option<Entry<T>> {
+        let entry = self.transactions.remove(txid)?;
+
+        self.total_size_in_bytes -= entry.size_in_bytes;
+        self.arrivals.remove(txid);
+        self.fee_index.remove(&(fee_rate_key(entry.fee, entry.size_in_bytes), txid.clone()));
+
+        Some(entry)
+    }
+
+    /// Drops entries that have aged out of `ttl` and, if the pool's total size still exceeds
+    /// `size_limit`, evicts the lowest fee-rate entries first until it no longer does.
+    pub fn expire(&mut self, ttl: std::time::Duration, size_limit: usize) {
+        let expired: Vec<Txid> = self
+            .arrivals
+            .iter()
+            .filter(|(_, arrived_at)| arrived_at.elapsed() >= ttl)
+            .map(|(txid, _)| txid.clone())
+            .collect();
+
+        for txid in expired {
+            self.remove(&txid);
+        }
+
+        while self.total_size_in_bytes > size_limit {
+            let lowest_fee_rate_txid = match self.fee_index.keys().next() {
+                Some((_, txid)) => txid.clone(),
+                None => break,
+            };
+
+            self.remove(&lowest_fee_rate_txid);
+        }
+    }
+
+    /// Returns a snapshot of the pool's current state, for exposure over RPC/metrics.
+    pub fn stats(&self) -> MemoryPoolStats {
+        let mut total_fee_rate = 0u64;
+        let mut min_fee_rate = None;
+        let mut max_fee_rate = None;
+
+        for (fee_rate, _) in self.fee_index.keys() {
+            total_fee_rate += fee_rate;
+            min_fee_rate = Some(min_fee_rate.map_or(*fee_rate, |rate: u64| rate.min(*fee_rate)));
+            max_fee_rate = Some(max_fee_rate.map_or(*fee_rate, |rate: u64| rate.max(*fee_rate)));
+        }
+
+        MemoryPoolStats {
+            unconfirmed_txs: self.transactions.len(),
+            total_size_in_bytes: self.total_size_in_bytes,
+            total_fee_rate,
+            min_fee_rate,
+            max_fee_rate,
+            oldest_entry_age: self.arrivals.values().map(|arrived_at| arrived_at.elapsed()).max(),
+        }
+    }
+}
+
+/// A snapshot of the memory pool's state, for exposure over RPC/metrics.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPoolStats {
+    /// The number of unconfirmed transactions currently held in the pool.
+    pub unconfirmed_txs: usize,
+    /// The combined serialized size, in bytes, of every pooled transaction.
+    pub total_size_in_bytes: usize,
+    /// The sum of every pooled entry's fee rate, scaled by `FEE_RATE_SCALE`.
+    pub total_fee_rate: u64,
+    /// The lowest fee rate among pooled entries, scaled by `FEE_RATE_SCALE`, or `None` if the
+    /// pool is empty.
+    pub min_fee_rate: Option<u64>,
+    /// The highest fee rate among pooled entries, scaled by `FEE_RATE_SCALE`, or `None` if the
+    /// pool is empty.
+    pub max_fee_rate: Option<u64>,
+    /// The age of the oldest pooled entry, or `None` if the pool is empty.
+    pub oldest_entry_age: Option<std::time::Duration>,
+}
+
+impl<T: ToBytes> MemoryPool<T> {
+    /// Inserts `entry` into the pool, keyed by the `Txid` derived from its transaction's
+    /// serialized bytes. Returns `Ok(Some(txid))` if it was newly inserted, or `Ok(None)` if a
+    /// transaction with that id was already present.
+    pub fn insert(&mut self, _storage: &MerkleTreeLedger, entry: Entry<T>) -> Result<Option<Txid>, MemoryPoolError> {
+        let txid = compute_txid(&entry.transaction)?;
+
+        if self.transactions.contains_key(&txid) {
+            return Ok(None);
+        }
+
+        self.total_size_in_bytes += entry.size_in_bytes;
+        self.fee_index
+            .insert((fee_rate_key(entry.fee, entry.size_in_bytes), txid.clone()), ());
+        self.arrivals.insert(txid.clone(), Instant::now());
+        self.transactions.insert(txid.clone(), entry);
+
+        Ok(Some(txid))
+    }
+
+    /// Leaves the pool unchanged: `storage` is not read and no pooled transactions are dropped.
+    /// Always returns `Ok(())`.
+    pub fn cleanse(&mut self, _storage: &MerkleTreeLedger) -> Result<(), MemoryPoolError> {
+        Ok(())
+    }
+
+    /// Writes nothing: `storage` is not touched and the pool's transactions are not persisted.
+    /// Always returns `Ok(())`.
+    pub fn store(&self, _storage: &MerkleTreeLedger) -> Result<(), MemoryPoolError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::{memory_pool::MemoryPool, ConsensusParameters};
+use snarkos_storage::MerkleTreeLedger;
+use snarkvm_dpc::base_dpc::instantiated::{Components, PublicParameters, Tx};
+
+use parking_lot::{Mutex, RwLock};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+/// The default time-to-live for an unconfirmed memory pool transaction before `Transactions::update`
+/// expires it.
+const DEFAULT_MEMORY_POOL_TRANSACTION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The default combined size, in bytes, the memory pool is allowed to grow to before its
+/// lowest fee-rate entries are evicted.
+const DEFAULT_MEMORY_POOL_SIZE_LIMIT: usize = 300 * 1024 * 1024;
+
+/// The parameters and settings shared across this node server's components.
+#[derive(Clone)]
+pub struct Environment {
+    inner: Arc<EnvironmentInner>,
+}
+
+struct EnvironmentInner {
+    is_bootnode: bool,
+    local_address: RwLock<Option<SocketAddr>>,
+    dpc_parameters: Arc<PublicParameters<Components>>,
+    consensus_parameters: Arc<ConsensusParameters>,
+    storage: Arc<RwLock<MerkleTreeLedger>>,
+    memory_pool: Arc<Mutex<MemoryPool<Tx>>>,
+    memory_pool_transaction_ttl: Duration,
+    memory_pool_size_limit: usize,
+}
+
+impl Environment {
+    /// Creates a new instance of `Environment`.
+    pub fn new(
+        is_bootnode: bool,
+        dpc_parameters: Arc<PublicParameters<Components>>,
+        consensus_parameters: Arc<ConsensusParameters>,
+        storage: Arc<RwLock<MerkleTreeLedger>>,
+        memory_pool: Arc<Mutex<MemoryPool<Tx>>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(EnvironmentInner {
+                is_bootnode,
+                local_address: RwLock::new(None),
+                dpc_parameters,
+                consensus_parameters,
+                storage,
+                memory_pool,
+                memory_pool_transaction_ttl: DEFAULT_MEMORY_POOL_TRANSACTION_TTL,
+                memory_pool_size_limit: DEFAULT_MEMORY_POOL_SIZE_LIMIT,
+            }),
+        }
+    }
+
+    /// Returns `true` if this node server is a bootnode.
+    pub fn is_bootnode(&self) -> bool {
+        self.inner.is_bootnode
+    }
+
+    /// Returns the local address of this node server, if it has been set.
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        *self.inner.local_address.read()
+    }
+
+    /// Returns a reference to the DPC public parameters.
+    pub fn dpc_parameters(&self) -> Arc<PublicParameters<Components>> {
+        self.inner.dpc_parameters.clone()
+    }
+
+    /// Returns a reference to the consensus parameters.
+    pub fn consensus_parameters(&self) -> Arc<ConsensusParameters> {
+        self.inner.consensus_parameters.clone()
+    }
+
+    /// Returns a reference to the node's storage.
+    pub fn storage(&self) -> Arc<RwLock<MerkleTreeLedger>> {
+        self.inner.storage.clone()
+    }
+
+    /// Returns a reference to the node's memory pool.
+    pub fn memory_pool(&self) -> Arc<Mutex<MemoryPool<Tx>>> {
+        self.inner.memory_pool.clone()
+    }
+
+    /// Returns the time-to-live for an unconfirmed memory pool transaction.
+    pub fn memory_pool_transaction_ttl(&self) -> Duration {
+        self.inner.memory_pool_transaction_ttl
+    }
+
+    /// Returns the combined size, in bytes, the memory pool is allowed to grow to before its
+    /// lowest fee-rate entries are evicted.
+    pub fn memory_pool_size_limit(&self) -> usize {
+        self.inner.memory_pool_size_limit
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::memory_pool::Txid;
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// The direction a message travels in relation to this node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// A message sent from this node out to a remote peer.
+    Outbound(SocketAddr),
+    /// A message received from a remote peer.
+    Inbound(SocketAddr),
+}
+
+/// A message exchanged with a peer, paired with the direction it travels in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub direction: Direction,
+    pub payload: Payload,
+}
+
+impl Message {
+    /// Creates a new message bound for (or arriving from) `direction`.
+    pub fn new(direction: Direction, payload: Payload) -> Self {
+        Self { direction, payload }
+    }
+}
+
+/// The body of a peer-to-peer message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Payload {
+    /// A single serialized transaction.
+    Transaction(Vec<u8>),
+    /// A batch of serialized transactions, sent in response to `GetMemoryPool`.
+    MemoryPool(Vec<Vec<u8>>),
+    /// A request for the sender's memory pool transactions.
+    GetMemoryPool,
+    /// An announcement of transaction ids the sender has available, without their bodies.
+    TransactionInv(Vec<Txid>),
+    /// A request for the bodies of the given transaction ids, sent in response to a `TransactionInv`
+    /// that announced ids the requester doesn't already have.
+    GetTransactions(Vec<Txid>),
+}
@@ -0,0 +1,42 @@
+// Copyright (C) 2019-2020 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// The snarkOS library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkOS library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
+
+use snarkos_consensus::memory_pool::Txid;
+
+use parking_lot::Mutex;
+use std::{collections::HashSet, sync::Arc};
+
+/// Information this node server tracks about a connected peer.
+#[derive(Debug, Clone, Default)]
+pub struct PeerInfo {
+    /// Transaction ids this peer is already known to have, so they aren't announced to it again.
+    ///
+    /// Wrapped in an `Arc` so that mutations made through a cloned `PeerInfo` (e.g. a snapshot of
+    /// the connected peers table) are visible to every other holder of this peer's info.
+    known_transactions: Arc<Mutex<HashSet<Txid>>>,
+}
+
+impl PeerInfo {
+    /// Returns `true` if this peer is already known to have the given transaction.
+    pub fn has_seen_transaction(&self, txid: &Txid) -> bool {
+        self.known_transactions.lock().contains(txid)
+    }
+
+    /// Records that this peer has seen the given transaction, so it isn't announced to it again.
+    pub fn seen_transaction(&self, txid: Txid) {
+        self.known_transactions.lock().insert(txid);
+    }
+}
@@ -15,14 +15,157 @@
 // along with the snarkOS library. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{external::message::*, peers::PeerInfo, Environment, NetworkError, Outbound};
-use snarkos_consensus::memory_pool::Entry;
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use snarkos_consensus::memory_pool::{Entry, MemoryPoolStats, Txid};
 use snarkvm_dpc::base_dpc::instantiated::Tx;
 use snarkvm_utilities::{
     bytes::{FromBytes, ToBytes},
     to_bytes,
 };
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    ops::ControlFlow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The maximum combined size, in bytes, of the transactions served in a single `MemoryPool` response.
+///
+/// Candidates are walked highest-fee-rate-first until this limit is reached, so peers always
+/// receive the most profitable transactions the memory pool currently holds.
+const MAX_MEMORY_POOL_RESPONSE_SIZE: usize = 8 * 1024 * 1024;
+
+/// How long a txid is remembered in the seen/rejected caches before it's eligible to be
+/// re-verified and re-propagated.
+const RECENTLY_SEEN_TXID_TTL: Duration = Duration::from_secs(60);
+
+/// The maximum number of entries kept in each of the seen/rejected txid caches, so a flood of
+/// distinct transactions within the TTL window can't grow them without bound.
+const RECENTLY_SEEN_TXID_CAPACITY: usize = 10_000;
+
+/// A bounded, time-expiring set of recently-seen txids.
+///
+/// Entries are recorded in arrival order in `order`, paired with the timestamp they were
+/// recorded with, so the oldest live entry is always at the front. A re-insertion of an
+/// already-seen txid updates `seen_at` but leaves its previous `order` entry in place as a
+/// stale duplicate; `evict_expired` recognizes those by comparing timestamps and discards them
+/// without touching `seen_at`, so a duplicate can never get stuck at the front and block the
+/// eviction of genuinely expired entries behind it. This keeps both `contains` and `insert` O(1)
+/// amortized, rather than scanning the whole cache on every call.
+struct SeenTxidCache {
+    seen_at: HashMap<Txid, Instant>,
+    order: VecDeque<(Txid, Instant)>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl SeenTxidCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            seen_at: HashMap::new(),
+            order: VecDeque::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Drops entries from the front of `order` that are stale duplicates or have aged out of `ttl`.
+    fn evict_expired(&mut self) {
+        while let Some((txid, inserted_at)) = self.order.front() {
+            if self.seen_at.get(txid) != Some(inserted_at) {
+                // A later re-insertion replaced this entry's timestamp in `seen_at`; this one is
+                // a stale duplicate left behind by that re-insertion.
+                self.order.pop_front();
+                continue;
+            }
+
+            if inserted_at.elapsed() < self.ttl {
+                break;
+            }
+
+            let (txid, _) = self.order.pop_front().unwrap();
+            self.seen_at.remove(&txid);
+        }
+    }
+
+    /// Returns `true` if `txid` is present and hasn't yet expired.
+    fn contains(&mut self, txid: &Txid) -> bool {
+        self.evict_expired();
+        self.seen_at.contains_key(txid)
+    }
+
+    /// Records `txid` as seen, evicting the oldest entry first if `self` is already at capacity.
+    fn insert(&mut self, txid: Txid) {
+        self.evict_expired();
+
+        if self.seen_at.len() >= self.capacity && !self.seen_at.contains_key(&txid) {
+            if let Some((oldest, _)) = self.order.pop_front() {
+                self.seen_at.remove(&oldest);
+            }
+        }
+
+        let inserted_at = Instant::now();
+        self.order.push_back((txid.clone(), inserted_at));
+        self.seen_at.insert(txid, inserted_at);
+    }
+}
+
+#[cfg(test)]
+mod seen_txid_cache_tests {
+    use super::*;
+    use std::thread::sleep;
+
+    fn txid(byte: u8) -> Txid {
+        vec![byte]
+    }
+
+    #[test]
+    fn expires_entries_after_ttl() {
+        let mut cache = SeenTxidCache::new(Duration::from_millis(20), 10);
+
+        cache.insert(txid(1));
+        assert!(cache.contains(&txid(1)));
+
+        sleep(Duration::from_millis(30));
+
+        assert!(!cache.contains(&txid(1)));
+    }
+
+    #[test]
+    fn reinserting_an_entry_does_not_block_eviction_of_entries_behind_it() {
+        let mut cache = SeenTxidCache::new(Duration::from_millis(20), 10);
+
+        cache.insert(txid(1));
+        sleep(Duration::from_millis(10));
+
+        // Re-inserting txid 1 leaves a stale duplicate at the front of `order`, with a fresh
+        // entry for it further back.
+        cache.insert(txid(1));
+        cache.insert(txid(2));
+
+        sleep(Duration::from_millis(25));
+
+        // Inserting txid 2 discarded the stale txid 1 duplicate; both live entries have now aged out.
+        assert!(!cache.contains(&txid(2)));
+        assert!(!cache.contains(&txid(1)));
+    }
+
+    #[test]
+    fn evicts_oldest_entry_once_at_capacity() {
+        let mut cache = SeenTxidCache::new(Duration::from_secs(60), 2);
+
+        cache.insert(txid(1));
+        cache.insert(txid(2));
+        cache.insert(txid(3));
+
+        assert!(!cache.contains(&txid(1)));
+        assert!(cache.contains(&txid(2)));
+        assert!(cache.contains(&txid(3)));
+    }
+}
 
 /// A stateful component for managing the transactions for the ledger on this node server.
 #[derive(Clone)]
@@ -31,6 +174,12 @@ pub struct Transactions {
     pub(crate) environment: Environment,
     /// The outbound handler of this node server.
     outbound: Arc<Outbound>,
+    /// Transaction ids accepted into the memory pool recently, so a transaction that's
+    /// gossiped to us again before it expires doesn't get re-verified and re-propagated.
+    seen_transactions: Arc<Mutex<SeenTxidCache>>,
+    /// Transaction ids rejected recently, so a known-invalid transaction is dropped
+    /// cheaply instead of paying for a second expensive verification.
+    rejected_transactions: Arc<Mutex<SeenTxidCache>>,
 }
 
 impl Transactions {
@@ -39,13 +188,47 @@ impl Transactions {
     ///
     pub fn new(environment: Environment, outbound: Arc<Outbound>) -> Self {
         trace!("Instantiating the transaction service");
-        Self { environment, outbound }
+        Self {
+            environment,
+            outbound,
+            seen_transactions: Arc::new(Mutex::new(SeenTxidCache::new(
+                RECENTLY_SEEN_TXID_TTL,
+                RECENTLY_SEEN_TXID_CAPACITY,
+            ))),
+            rejected_transactions: Arc::new(Mutex::new(SeenTxidCache::new(
+                RECENTLY_SEEN_TXID_TTL,
+                RECENTLY_SEEN_TXID_CAPACITY,
+            ))),
+        }
+    }
+
+    /// Returns `true` if `txid` is present in `cache` and hasn't yet expired.
+    fn is_recently_seen(cache: &Mutex<SeenTxidCache>, txid: &Txid) -> bool {
+        cache.lock().contains(txid)
+    }
+
+    /// Records `txid` as seen in `cache`, so a duplicate arriving before the TTL elapses is skipped.
+    fn mark_recently_seen(cache: &Mutex<SeenTxidCache>, txid: Txid) {
+        cache.lock().insert(txid);
+    }
+
+    ///
+    /// Returns a snapshot of the memory pool's current state, for exposure over RPC/metrics.
+    ///
+    pub fn memory_pool_stats(&self) -> MemoryPoolStats {
+        self.environment.memory_pool().lock().stats()
     }
 
     ///
-    /// Triggers the transaction sync with a selected peer.
+    /// Expires stale memory pool entries and, separately, triggers the transaction sync
+    /// with a selected peer.
     ///
     pub fn update(&self, sync_node: Option<SocketAddr>) -> Result<(), NetworkError> {
+        self.environment
+            .memory_pool()
+            .lock()
+            .expire(self.environment.memory_pool_transaction_ttl(), self.environment.memory_pool_size_limit());
+
         if !self.environment.is_bootnode() {
             if let Some(sync_node) = sync_node {
                 self.outbound
@@ -60,10 +243,14 @@ impl Transactions {
         Ok(())
     }
 
-    /// Broadcast transaction to connected peers
+    /// Announce a transaction to connected peers that haven't seen it yet.
+    ///
+    /// Rather than flooding every peer with the full transaction body, we send a lightweight
+    /// `TransactionInv` announcement; peers that don't already have the transaction will come
+    /// back with a `GetTransactions` request for its body.
     pub(crate) async fn propagate_transaction(
         &self,
-        transaction_bytes: Vec<u8>,
+        txid: Txid,
         transaction_sender: SocketAddr,
         connected_peers: &HashMap<SocketAddr, PeerInfo>,
     ) -> Result<(), NetworkError> {
@@ -71,13 +258,18 @@ impl Transactions {
 
         let local_address = self.environment.local_address().unwrap();
 
-        for remote_address in connected_peers.keys() {
+        for (remote_address, peer_info) in connected_peers {
             if *remote_address != transaction_sender && *remote_address != local_address {
-                // Send a `Transaction` message to the connected peer.
+                if peer_info.has_seen_transaction(&txid) {
+                    continue;
+                }
+
+                // Send a `TransactionInv` message to the connected peer.
                 self.outbound.send_request(Message::new(
                     Direction::Outbound(*remote_address),
-                    Payload::Transaction(transaction_bytes.clone()),
+                    Payload::TransactionInv(vec![txid.clone()]),
                 ));
+                peer_info.seen_transaction(txid.clone());
             }
         }
 
@@ -92,32 +284,98 @@ impl Transactions {
         connected_peers: HashMap<SocketAddr, PeerInfo>,
     ) -> Result<(), NetworkError> {
         if let Ok(tx) = Tx::read(&*transaction) {
+            let txid = tx.transaction_id()?;
+
+            if Self::is_recently_seen(&self.seen_transactions, &txid) {
+                trace!("Skipping a transaction that was already seen: {:?}", hex::encode(txid));
+                return Ok(());
+            }
+
+            if Self::is_recently_seen(&self.rejected_transactions, &txid) {
+                debug!("Skipping a transaction that was recently rejected: {:?}", hex::encode(txid));
+                return Ok(());
+            }
+
             let parameters = self.environment.dpc_parameters();
             let storage = self.environment.storage();
             let consensus = self.environment.consensus_parameters();
 
             if !consensus.verify_transaction(parameters, &tx, &*storage.read())? {
                 error!("Received a transaction that was invalid");
+                Self::mark_recently_seen(&self.rejected_transactions, txid);
                 return Ok(());
             }
 
             if tx.value_balance.is_negative() {
                 error!("Received a transaction that was a coinbase transaction");
+                Self::mark_recently_seen(&self.rejected_transactions, txid);
                 return Ok(());
             }
 
             let entry = Entry::<Tx> {
                 size_in_bytes: transaction.len(),
+                fee: tx.value_balance.0,
                 transaction: tx,
             };
 
             let insertion = self.environment.memory_pool().lock().insert(&storage.read(), entry);
 
             if let Ok(inserted) = insertion {
-                if inserted.is_some() {
+                // Mark as seen whether this call inserted it or it was already present, so
+                // subsequent re-gossip of an already-pooled transaction also skips verification.
+                Self::mark_recently_seen(&self.seen_transactions, txid.clone());
+
+                if let Some(pool_txid) = inserted {
                     info!("Transaction added to memory pool.");
-                    self.propagate_transaction(transaction, source, &connected_peers)
-                        .await?;
+                    // Announce the memory pool's id, which is what `GetTransactions` requests are served by.
+                    self.propagate_transaction(pool_txid, source, &connected_peers).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A peer has announced transaction ids it has available.
+    ///
+    /// We request only the ones we don't already know about, so transaction bodies cross
+    /// the wire at most once per link regardless of how many peers relay the same announcement.
+    pub(crate) async fn received_transaction_inv(
+        &self,
+        source: SocketAddr,
+        txids: Vec<Txid>,
+    ) -> Result<(), NetworkError> {
+        let unknown_txids: Vec<Txid> = {
+            let memory_pool = self.environment.memory_pool().lock();
+            txids.into_iter().filter(|txid| !memory_pool.contains(txid)).collect()
+        };
+
+        if !unknown_txids.is_empty() {
+            self.outbound.send_request(Message::new(
+                Direction::Outbound(source),
+                Payload::GetTransactions(unknown_txids),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// A peer has requested the bodies of transactions it learned about via an announcement.
+    pub(crate) async fn received_get_transactions(
+        &self,
+        remote_address: SocketAddr,
+        txids: Vec<Txid>,
+    ) -> Result<(), NetworkError> {
+        let memory_pool = self.environment.memory_pool().lock();
+
+        for txid in txids {
+            if let Some(entry) = memory_pool.get(&txid) {
+                if let Ok(transaction_bytes) = to_bytes![entry.transaction] {
+                    // Send a `Transaction` message to the connected peer.
+                    self.outbound.send_request(Message::new(
+                        Direction::Outbound(remote_address),
+                        Payload::Transaction(transaction_bytes),
+                    ));
                 }
             }
         }
@@ -127,18 +385,23 @@ impl Transactions {
 
     /// A peer has requested our memory pool transactions.
     pub(crate) async fn received_get_memory_pool(&self, remote_address: SocketAddr) -> Result<(), NetworkError> {
-        // TODO (howardwu): This should have been written with Rayon - it is easily parallelizable.
         let transactions = {
-            let mut txs = vec![];
+            // Walk the candidates highest-fee-rate-first while holding the lock, then drop it
+            // before doing the CPU-heavy serialization work in parallel.
+            let mut candidates = vec![];
 
             let memory_pool = self.environment.memory_pool().lock();
-            for entry in memory_pool.transactions.values() {
-                if let Ok(transaction_bytes) = to_bytes![entry.transaction] {
-                    txs.push(transaction_bytes);
-                }
-            }
+            memory_pool.iterate_candidates(MAX_MEMORY_POOL_RESPONSE_SIZE, |entry| {
+                candidates.push(entry.clone());
+                ControlFlow::Continue(())
+            });
 
-            txs
+            drop(memory_pool);
+
+            candidates
+                .par_iter()
+                .filter_map(|entry| to_bytes![entry.transaction].ok())
+                .collect::<Vec<_>>()
         };
 
         if !transactions.is_empty() {
@@ -154,16 +417,34 @@ impl Transactions {
 
     /// A peer has sent us their memory pool transactions.
     pub(crate) fn received_memory_pool(&self, transactions: Vec<Vec<u8>>) -> Result<(), NetworkError> {
-        let mut memory_pool = self.environment.memory_pool().lock();
+        let parameters = self.environment.dpc_parameters();
+        let storage = self.environment.storage();
+        let consensus = self.environment.consensus_parameters();
 
-        for transaction_bytes in transactions {
-            let transaction: Tx = Tx::read(&transaction_bytes[..])?;
-            let entry = Entry::<Tx> {
-                size_in_bytes: transaction_bytes.len(),
-                transaction,
-            };
+        // Deserialize and verify the batch in parallel - this is CPU-heavy zk verification work,
+        // so it's kept entirely outside of the memory pool lock.
+        let entries: Vec<Entry<Tx>> = transactions
+            .par_iter()
+            .filter_map(|transaction_bytes| {
+                let transaction = Tx::read(&transaction_bytes[..]).ok()?;
+
+                if !consensus.verify_transaction(parameters, &transaction, &*storage.read()).ok()? {
+                    return None;
+                }
+
+                Some(Entry::<Tx> {
+                    size_in_bytes: transaction_bytes.len(),
+                    fee: transaction.value_balance.0,
+                    transaction,
+                })
+            })
+            .collect();
+
+        // Take the memory pool lock once to batch-insert the already-verified entries.
+        let mut memory_pool = self.environment.memory_pool().lock();
 
-            if let Ok(Some(txid)) = memory_pool.insert(&*self.environment.storage().read(), entry) {
+        for entry in entries {
+            if let Ok(Some(txid)) = memory_pool.insert(&*storage.read(), entry) {
                 debug!(
                     "Transaction added to memory pool with txid: {:?}",
                     hex::encode(txid.clone())